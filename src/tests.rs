@@ -0,0 +1,1614 @@
+use super::*;
+
+use axum::body::{to_bytes, Body};
+use axum::extract::connect_info::MockConnectInfo;
+use proptest::prelude::*;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+fn test_app() -> Router {
+    app(Config::default()).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))))
+}
+
+async fn send(request: axum::http::Request<Body>) -> (StatusCode, Value) {
+    let response = test_app().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, body)
+}
+
+async fn post(path: &str, body: Value) -> (StatusCode, Value) {
+    send(axum::http::Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()).await
+}
+
+async fn get(path: &str) -> (StatusCode, Value) {
+    send(axum::http::Request::get(path).body(Body::empty()).unwrap()).await
+}
+
+fn pubkey() -> String {
+    Pubkey::new_unique().to_string()
+}
+
+fn assert_error(status: StatusCode, body: &Value, expected: StatusCode, message: &str) {
+    assert_eq!(status, expected, "{}", body);
+    assert_eq!(body["success"], false);
+    assert_eq!(body["error"], message);
+}
+
+// ---------------
+// infra
+
+#[tokio::test]
+async fn health_reports_ok() {
+    let (status, body) = get("/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "ok");
+}
+
+#[tokio::test]
+async fn version_reports_build_info() {
+    let (status, body) = get("/version").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["version"], env!("CARGO_PKG_VERSION"));
+    assert!(body["data"]["build_timestamp"].as_u64().unwrap() > 0);
+    assert!(body["data"]["solana_sdk_version"].as_str().unwrap().starts_with("2."));
+    assert!(!body["data"]["git_sha"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn envelopes_carry_api_version() {
+    let response = test_app().oneshot(axum::http::Request::get("/health").body(Body::empty()).unwrap()).await.unwrap();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(bytes.starts_with(b"{\"success\":true,\"api_version\":\"1\",\"data\":"));
+
+    let (_, body) = get("/nope").await;
+    assert_eq!(body["api_version"], API_VERSION);
+}
+
+#[tokio::test]
+async fn openapi_lists_every_endpoint() {
+    let (status, body) = get("/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+    for path in ["/keypair", "/token/create", "/message/sign", "/send/token", "/token/set-authority"] {
+        assert!(body["paths"].get(path).is_some(), "missing {}", path);
+    }
+}
+
+#[tokio::test]
+async fn metrics_count_requests_by_route() {
+    post("/pubkey/validate", json!({ "pubkey": pubkey() })).await;
+    post("/token/ata", json!({ "owner": "nope", "mint": pubkey() })).await;
+
+    let response = test_app().oneshot(axum::http::Request::get("/metrics").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8(bytes.to_vec()).unwrap();
+    assert!(text.contains("http_requests_total{method=\"POST\",path=\"/pubkey/validate\",status=\"200\"}"), "{}", text);
+    assert!(text.contains("http_request_errors_total{method=\"POST\",path=\"/token/ata\",status=\"422\",code=\"invalid_pubkey\"}"), "{}", text);
+    assert!(text.contains("http_request_duration_seconds_bucket"), "{}", text);
+}
+
+#[tokio::test]
+async fn request_id_is_echoed_and_allowed_cross_origin() {
+    let with_id = |request: axum::http::request::Builder, body: Body| request
+        .header("x-request-id", "client-chosen-id")
+        .header(header::ORIGIN, "https://app.example")
+        .body(body)
+        .unwrap();
+
+    let response = test_app().oneshot(with_id(axum::http::Request::get("/health"), Body::empty())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-request-id"], "client-chosen-id");
+    assert_eq!(response.headers()[header::ACCESS_CONTROL_EXPOSE_HEADERS], "x-request-id");
+
+    let request = axum::http::Request::post("/token/ata").header(header::CONTENT_TYPE, "application/json");
+    let response = test_app().oneshot(with_id(request, Body::from(json!({ "owner": "nope", "mint": pubkey() }).to_string()))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.headers()["x-request-id"], "client-chosen-id");
+
+    let preflight = axum::http::Request::builder()
+        .method(Method::OPTIONS)
+        .uri("/message/sign")
+        .header(header::ORIGIN, "https://app.example")
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+        .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type,x-request-id")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app().oneshot(preflight).await.unwrap();
+    let allowed = response.headers()[header::ACCESS_CONTROL_ALLOW_HEADERS].to_str().unwrap().to_string();
+    assert!(allowed.contains("x-request-id"), "{}", allowed);
+}
+
+#[tokio::test]
+async fn unknown_route_is_json_404() {
+    let (status, body) = get("/nope").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn wrong_method_is_json_405() {
+    let (status, body) = get("/keypair").await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(body["code"], "METHOD_NOT_ALLOWED");
+}
+
+#[tokio::test]
+async fn panics_become_json_500() {
+    let response = handle_panic(Box::new("index out of bounds"));
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_error(StatusCode::INTERNAL_SERVER_ERROR, &body, StatusCode::INTERNAL_SERVER_ERROR, "Internal server error");
+}
+
+#[tokio::test]
+async fn slow_requests_time_out() {
+    let app = Router::new()
+        .route("/slow", axum::routing::get(|| async { tokio::time::sleep(Duration::from_secs(1)).await }))
+        .layer(middleware::from_fn_with_state(Duration::from_millis(20), request_timeout));
+
+    let response = app.oneshot(axum::http::Request::get("/slow").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["code"], "TIMEOUT");
+}
+
+#[tokio::test]
+async fn malformed_json_is_400() {
+    let (status, body) = send(axum::http::Request::post("/message/sign")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from("{not json"))
+        .unwrap()).await;
+    assert_error(status, &body, StatusCode::BAD_REQUEST, "Malformed JSON body: key must be a string at line 1 column 2");
+    assert_eq!(body["code"], "MALFORMED_JSON");
+}
+
+#[tokio::test]
+async fn wrong_field_type_is_400_with_field_name() {
+    let (status, body) = post("/token/create", json!({ "mintAuthority": pubkey(), "mint": pubkey(), "decimals": "six" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["code"], "INVALID_JSON_DATA");
+    assert!(body["error"].as_str().unwrap().contains("decimals"), "{}", body);
+}
+
+#[tokio::test]
+async fn missing_content_type_is_415() {
+    let (status, body) = send(axum::http::Request::post("/message/sign")
+        .body(Body::from(json!({ "message": "hi", "secret": "x" }).to_string()))
+        .unwrap()).await;
+    assert_error(status, &body, StatusCode::UNSUPPORTED_MEDIA_TYPE, "Expected request with `Content-Type: application/json`");
+    assert_eq!(body["code"], "MISSING_CONTENT_TYPE");
+}
+
+#[tokio::test]
+async fn wrong_content_type_is_415() {
+    let (status, body) = send(axum::http::Request::post("/message/verify")
+        .header("content-type", "text/plain")
+        .body(Body::from(json!({ "message": "hi", "signature": "x", "pubkey": pubkey() }).to_string()))
+        .unwrap()).await;
+    assert_error(status, &body, StatusCode::UNSUPPORTED_MEDIA_TYPE, "Unsupported Content-Type `text/plain`, expected `application/json`");
+    assert_eq!(body["code"], "UNSUPPORTED_CONTENT_TYPE");
+
+    // bodyless requests don't need one
+    let (status, _) = send(axum::http::Request::post("/keypair").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // parameters and +json subtypes are fine, as they are for the Json extractor
+    let (status, _) = send(axum::http::Request::post("/pubkey/validate")
+        .header("content-type", "application/json; charset=utf-8")
+        .body(Body::from(json!({ "pubkey": pubkey() }).to_string()))
+        .unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn oversized_body_is_413() {
+    let (status, body) = post("/message/sign", json!({ "message": "a".repeat(20_000), "secret": "x" })).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["code"], "PAYLOAD_TOO_LARGE");
+}
+
+#[tokio::test]
+async fn large_responses_are_compressed() {
+    let request = |path: &str| axum::http::Request::get(path)
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = test_app().oneshot(request("/openapi.json")).await.unwrap();
+    assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+
+    let response = test_app().oneshot(request("/health")).await.unwrap();
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+}
+
+#[tokio::test]
+async fn cache_control_depends_on_the_route() {
+    let post = |path: &str, body: Value| axum::http::Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let response = test_app().oneshot(axum::http::Request::get("/token/account-sizes").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.headers()[header::CACHE_CONTROL], "public, max-age=3600");
+
+    let response = test_app().oneshot(axum::http::Request::post("/keypair").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+
+    let response = test_app().oneshot(post("/message/sign", json!({ "message": "m", "secret": bs58::encode(Keypair::new().to_bytes()).into_string() }))).await.unwrap();
+    assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+
+    // rejected before any handler runs, still never stored
+    let response = test_app().oneshot(axum::http::Request::post("/message/sign")
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(Body::from("hi"))
+        .unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+
+    // GET lookups are cacheable, but only when they succeed
+    let response = test_app().oneshot(axum::http::Request::get(format!("/pubkey/validate?pubkey={}", pubkey())).body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CACHE_CONTROL], "public, max-age=3600");
+    let response = test_app().oneshot(axum::http::Request::get("/token/ata?owner=nope&mint=nope").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+
+    // POST lookups and instruction builders say nothing either way
+    let response = test_app().oneshot(post("/pubkey/validate", json!({ "pubkey": pubkey() }))).await.unwrap();
+    assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+    let response = test_app().oneshot(post("/send/sol", json!({ "from": pubkey(), "to": pubkey(), "lamports": 1 }))).await.unwrap();
+    assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+}
+
+#[tokio::test]
+async fn timed_out_signing_keeps_its_permit_until_done() {
+    let state = Arc::new(AppState::new(Config { signing_workers: 1, ..Config::default() }));
+    let (release, wait) = std::sync::mpsc::channel::<()>();
+
+    let busy = run_blocking(&state, move |_| {
+        wait.recv().ok();
+        Ok(())
+    });
+    assert!(tokio::time::timeout(Duration::from_millis(20), busy).await.is_err());
+
+    // the request is gone but the closure is still running
+    assert_eq!(state.signing_permits.available_permits(), 0);
+
+    release.send(()).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while state.signing_permits.available_permits() == 0 {
+        assert!(Instant::now() < deadline, "permit never came back");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+#[test]
+fn sensitive_routes_match_whole_segments() {
+    for path in ["/keypair", "/keypair/batch", "/message/sign", "/message/sign/multi"] {
+        assert!(is_sensitive_route(path), "{}", path);
+    }
+    for path in ["/keypairs", "/message/signature", "/message/verify", "/health"] {
+        assert!(!is_sensitive_route(path), "{}", path);
+    }
+}
+
+#[tokio::test]
+async fn lookups_answer_get_and_post_alike() {
+    let (program_id, owner, mint) = (pubkey(), pubkey(), pubkey());
+    let cases = [
+        ("/pda/derive", json!({ "program_id": program_id, "seeds": ["vault", owner] }), format!("/pda/derive?program_id={}&seeds=vault,{}", program_id, owner)),
+        ("/token/ata", json!({ "owner": owner, "mint": mint }), format!("/token/ata?owner={}&mint={}", owner, mint)),
+        ("/pubkey/validate", json!({ "pubkey": owner }), format!("/pubkey/validate?pubkey={}", owner)),
+    ];
+    for (path, body, query) in cases {
+        let (status, from_post) = post(path, body).await;
+        assert_eq!(status, StatusCode::OK, "{}", from_post);
+        let (status, from_get) = get(&query).await;
+        assert_eq!(status, StatusCode::OK, "{}", from_get);
+        assert_eq!(from_get, from_post, "{}", path);
+    }
+}
+
+#[tokio::test]
+async fn global_rate_limit_is_per_ip_with_retry_after() {
+    let app = app(Config { rate_limit_rps: 2.0, ..Config::default() });
+    let from = |ip: [u8; 4]| app.clone().layer(MockConnectInfo(SocketAddr::from((ip, 0))));
+    let health = || axum::http::Request::get("/health").body(Body::empty()).unwrap();
+
+    // a burst of two is allowed, the third is refused
+    for _ in 0..2 {
+        assert_eq!(from([10, 0, 0, 1]).oneshot(health()).await.unwrap().status(), StatusCode::OK);
+    }
+    let response = from([10, 0, 0, 1]).oneshot(health()).await.unwrap();
+    let status = response.status();
+    assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_error(status, &body, StatusCode::TOO_MANY_REQUESTS, "Too many requests");
+    assert_eq!(body["code"], "RATE_LIMITED");
+
+    // another client has its own bucket
+    assert_eq!(from([10, 0, 0, 2]).oneshot(health()).await.unwrap().status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn signing_has_its_own_rate_limit() {
+    let config = Config { sign_rate_limit_rps: Some(1.0), ..Config::default() };
+    let app = app(config).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))));
+    let request = |path: &str, body: Value| axum::http::Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let secret = bs58::encode(Keypair::new().to_bytes()).into_string();
+    let sign = json!({ "message": "hi", "secret": secret });
+    let response = app.clone().oneshot(request("/message/sign", sign.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.clone().oneshot(request("/message/sign", sign)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let response = app.clone().oneshot(request("/pubkey/validate", json!({ "pubkey": pubkey() }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn text_length_limit_counts_characters() {
+    assert!(!is_suspicious_text(&"a".repeat(1000), false));
+    assert!(is_suspicious_text(&"a".repeat(1001), false));
+    // 4 bytes each, so well over 1000 bytes but exactly 1000 characters
+    assert!(!is_suspicious_text(&"🦀".repeat(1000), false));
+    assert!(is_suspicious_text(&"🦀".repeat(1001), false));
+    assert!(!is_suspicious_text(&"漢字".repeat(500), false));
+    assert!(is_suspicious_text("bell\u{7}", false));
+}
+
+#[tokio::test]
+async fn disabled_endpoints_return_403() {
+    let config = Config { disable_keypair: true, disable_sign: true, ..Config::default() };
+    let app = app(config).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))));
+    let request = |path: &str, body: Value| axum::http::Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    for path in ["/keypair", "/keypair/vanity", "/message/sign", "/message/sign/batch"] {
+        let response = app.clone().oneshot(request(path, json!({}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{}", path);
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["code"], "ENDPOINT_DISABLED");
+    }
+
+    // verification never touches a secret, so it stays up
+    let response = app.clone().oneshot(request("/message/verify", json!({}))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+// ---------------
+// keypairs
+
+#[tokio::test]
+async fn keypair_generates_base58_by_default() {
+    let (status, body) = post("/keypair", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    let secret = bs58::decode(body["data"]["secret"].as_str().unwrap()).into_vec().unwrap();
+    let keypair = Keypair::try_from(secret.as_slice()).unwrap();
+    assert_eq!(body["data"]["pubkey"], keypair.pubkey().to_string());
+}
+
+#[tokio::test]
+async fn keypair_supports_json_array() {
+    let (status, body) = post("/keypair?format=json_array", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["secret"].as_array().unwrap().len(), 64);
+}
+
+#[tokio::test]
+async fn keypair_rejects_unknown_format() {
+    let (status, body) = post("/keypair?format=hex", json!({})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+}
+
+#[tokio::test]
+async fn keypair_from_seed_is_deterministic() {
+    let seed = "11".repeat(32);
+    let (_, first) = post("/keypair/from-seed", json!({ "seed": seed })).await;
+    let (status, second) = post("/keypair/from-seed", json!({ "seed": seed })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["data"]["pubkey"], second["data"]["pubkey"]);
+}
+
+#[tokio::test]
+async fn keypair_from_seed_rejects_short_seed() {
+    let (status, body) = post("/keypair/from-seed", json!({ "seed": "abcd" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Seed must be exactly 32 bytes");
+}
+
+#[tokio::test]
+async fn keypair_recover_returns_pubkey() {
+    let keypair = Keypair::new();
+    let (status, body) = post("/keypair/recover", json!({ "secret": bs58::encode(keypair.to_bytes()).into_string() })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["pubkey"], keypair.pubkey().to_string());
+
+    let (status, body) = post("/keypair/recover", json!({ "secret": serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap() })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["pubkey"], keypair.pubkey().to_string());
+
+    let (status, body) = post("/keypair/recover", json!({ "secret": bs58::encode([1u8; 32]).into_string() })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid secret key");
+}
+
+#[tokio::test]
+async fn keypair_batch_generates_distinct_keys() {
+    let (status, body) = post("/keypair/batch", json!({ "count": 5 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let keypairs = body["data"].as_array().unwrap();
+    assert_eq!(keypairs.len(), 5);
+    let unique: HashSet<&str> = keypairs.iter().map(|k| k["pubkey"].as_str().unwrap()).collect();
+    assert_eq!(unique.len(), 5);
+
+    let (status, body) = post("/keypair/batch", json!({ "count": 0 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "count must be greater than 0");
+
+    let (status, body) = post("/keypair/batch", json!({ "count": 101 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Too many keypairs (max 100)");
+}
+
+const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+#[tokio::test]
+async fn keypair_from_mnemonic_matches_wallet_derivation() {
+    let (status, body) = post("/keypair/from-mnemonic", json!({ "mnemonic": TEST_MNEMONIC })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["pubkey"], "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk");
+
+    // a different account index or passphrase gives a different key
+    let (_, other) = post("/keypair/from-mnemonic", json!({ "mnemonic": TEST_MNEMONIC, "derivation_path": "m/44'/501'/1'/0'" })).await;
+    assert_ne!(other["data"]["pubkey"], body["data"]["pubkey"]);
+    let (_, other) = post("/keypair/from-mnemonic", json!({ "mnemonic": TEST_MNEMONIC, "passphrase": "extra" })).await;
+    assert_ne!(other["data"]["pubkey"], body["data"]["pubkey"]);
+}
+
+#[tokio::test]
+async fn keypair_from_mnemonic_rejects_bad_input() {
+    let bad_checksum = TEST_MNEMONIC.replace("about", "abandon");
+    let (status, body) = post("/keypair/from-mnemonic", json!({ "mnemonic": bad_checksum })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+    assert!(body["error"].as_str().unwrap().starts_with("Invalid mnemonic"));
+
+    let unknown_word = TEST_MNEMONIC.replace("about", "solanaa");
+    let (status, _) = post("/keypair/from-mnemonic", json!({ "mnemonic": unknown_word })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, _) = post("/keypair/from-mnemonic", json!({ "mnemonic": TEST_MNEMONIC, "derivation_path": "44'/501'" })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn vanity_keypair_matches_prefix() {
+    let (status, body) = post("/keypair/vanity", json!({ "prefix": "A" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let address = body["data"]["pubkey"].as_str().unwrap();
+    assert!(address.starts_with('A'));
+
+    let secret = bs58::decode(body["data"]["secret"].as_str().unwrap()).into_vec().unwrap();
+    assert_eq!(Keypair::try_from(secret.as_slice()).unwrap().pubkey().to_string(), address);
+
+    let (status, body) = post("/keypair/vanity", json!({ "prefix": "a", "case_sensitive": false })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["data"]["pubkey"].as_str().unwrap().to_lowercase().starts_with('a'));
+}
+
+#[tokio::test]
+async fn vanity_keypair_validates_prefix() {
+    let (status, body) = post("/keypair/vanity", json!({ "prefix": "0x" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "prefix must only contain base58 characters");
+
+    let (status, body) = post("/keypair/vanity", json!({ "prefix": "abcdefgh" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "prefix must be 1 to 3 characters");
+
+    // three leading '1's need three zero bytes (1 in 2^24) and a late letter like 'z'
+    // only starts the short 43-character addresses, so neither can be found in time
+    for prefix in ["111", "zzz"] {
+        let (status, body) = post("/keypair/vanity", json!({ "prefix": prefix })).await;
+        assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "prefix is too rare to find within the search limit");
+    }
+}
+
+#[test]
+fn vanity_expected_attempts_reflect_base58_layout() {
+    assert_eq!(vanity_expected_attempts("1", true), 256.0);
+    assert_eq!(vanity_expected_attempts("111", true), 16_777_216.0);
+
+    // 'A' heads ~1 in 17 of the 44-character addresses, 'z' only ~1 in 58 of the 43-character ones
+    assert!((15.0..20.0).contains(&vanity_expected_attempts("A", true)));
+    assert!((900.0..1100.0).contains(&vanity_expected_attempts("z", true)));
+
+    // case-insensitive searches accept either case, so they can only get cheaper
+    assert!(vanity_expected_attempts("ab", false) < vanity_expected_attempts("ab", true));
+    assert!(vanity_expected_attempts("AAA", true) <= MAX_VANITY_EXPECTED_ATTEMPTS);
+}
+
+// ---------------
+// tokens
+
+#[tokio::test]
+async fn create_token_builds_initialize_mint() {
+    let mint = pubkey();
+    let (status, body) = post("/token/create", json!({ "mintAuthority": pubkey(), "mint": mint, "decimals": 6 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["program_id"], spl_token::id().to_string());
+    assert_eq!(body["data"]["accounts"][0]["pubkey"], mint);
+    assert_eq!(body["data"]["accounts"][0]["role"], "mint");
+    assert_eq!(body["data"]["rent_exempt_lamports"], Rent::default().minimum_balance(Mint::LEN));
+
+    let notes = body["data"]["notes"].as_array().unwrap();
+    assert!(notes[0].as_str().unwrap().starts_with("mint: must already be a created account owned by the token program"));
+    assert!(notes[0].as_str().unwrap().contains(&spl_token::id().to_string()));
+}
+
+#[tokio::test]
+async fn create_token_validates_input() {
+    let (status, body) = post("/token/create", json!({ "mint": pubkey(), "decimals": 6 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Missing required field: mintAuthority");
+    assert_eq!(body["code"], "MISSING_FIELD");
+
+    let (status, body) = post("/token/create", json!({ "mintAuthority": pubkey(), "mint": pubkey(), "decimals": 10 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid decimals value (max 9)");
+    assert_eq!(body["code"], "INVALID_DECIMALS");
+
+    let (status, body) = post("/token/create", json!({ "mintAuthority": "nope", "mint": pubkey(), "decimals": 6 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid mint authority");
+    assert_eq!(body["code"], "INVALID_PUBKEY");
+
+    let same = pubkey();
+    let (status, body) = post("/token/create", json!({ "mintAuthority": same, "mint": same, "decimals": 6 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "mint and mint authority must differ");
+}
+
+#[tokio::test]
+async fn create_token_can_return_raw_bytes() {
+    let response = test_app().oneshot(axum::http::Request::post("/token/create")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCEPT, "application/octet-stream")
+        .body(Body::from(json!({ "mintAuthority": pubkey(), "mint": pubkey(), "decimals": 6 }).to_string()))
+        .unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/octet-stream");
+}
+
+#[tokio::test]
+async fn create_token_full_creates_then_initializes() {
+    let mint = pubkey();
+    let (status, body) = post("/token/create-full", json!({ "payer": pubkey(), "mintAuthority": pubkey(), "mint": mint, "decimals": 6 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let instructions = body["data"]["instructions"].as_array().unwrap();
+    assert_eq!(instructions[0]["program_id"], solana_system_interface::program::id().to_string());
+    assert_eq!(instructions[0]["accounts"][1]["pubkey"], mint);
+    assert_eq!(instructions[1]["program_id"], spl_token::id().to_string());
+
+    let (status, body) = post("/token/create-full", json!({ "mintAuthority": pubkey(), "mint": mint, "decimals": 6 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Missing required field: payer");
+}
+
+#[tokio::test]
+async fn create_token_supports_token_2022() {
+    let (status, body) = post("/token/create", json!({ "mintAuthority": pubkey(), "mint": pubkey(), "decimals": 6, "program": "token-2022" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["program_id"], spl_token_2022::id().to_string());
+
+    // the mint account has to be owned by the program that initializes it
+    let (status, body) = post("/token/create-full", json!({ "payer": pubkey(), "mintAuthority": pubkey(), "mint": pubkey(), "decimals": 6, "program": "token-2022" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let create = general_purpose::STANDARD.decode(body["data"]["instructions"][0]["instruction_data"].as_str().unwrap()).unwrap();
+    assert!(create.ends_with(spl_token_2022::id().as_ref()));
+    assert_eq!(body["data"]["instructions"][1]["program_id"], spl_token_2022::id().to_string());
+
+    let (status, body) = post("/token/create", json!({ "mintAuthority": pubkey(), "mint": pubkey(), "decimals": 6, "program": "token-2023" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Unsupported program 'token-2023', expected token or token-2022");
+}
+
+#[tokio::test]
+async fn create_token_full_flag_returns_both_instructions() {
+    let (status, body) = post("/token/create", json!({ "payer": pubkey(), "mintAuthority": pubkey(), "mint": pubkey(), "decimals": 6, "full": true })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["instructions"].as_array().unwrap().len(), 2);
+
+    let (status, body) = post("/token/create", json!({ "mintAuthority": pubkey(), "mint": pubkey(), "decimals": 6, "full": true })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Missing required field: payer");
+}
+
+#[tokio::test]
+async fn max_decimals_is_configurable() {
+    let config = Config { max_decimals: 18, ..Config::default() };
+    let response = app(config)
+        .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))))
+        .oneshot(axum::http::Request::post("/token/create")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!({ "mintAuthority": pubkey(), "mint": pubkey(), "decimals": 18 }).to_string()))
+            .unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn mint_token_builds_mint_to() {
+    let (status, body) = post("/token/mint", json!({ "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": 1000 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["accounts"].as_array().unwrap().len(), 3);
+
+    let data = general_purpose::STANDARD.decode(body["data"]["instruction_data"].as_str().unwrap()).unwrap();
+    assert_eq!(body["data"]["data_len"], data.len());
+    assert_eq!(body["data"]["data_sha256"], encode_hex(hash(&data).as_ref()));
+}
+
+#[tokio::test]
+async fn mint_token_uses_checked_variant_with_expected_decimals() {
+    let (status, body) = post("/token/mint", json!({ "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": 100 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["data"]["warning"].as_str().unwrap().starts_with("decimals unknown"));
+
+    let (status, body) = post("/token/mint", json!({ "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": 100, "expected_decimals": 6 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["data"].get("warning").is_none());
+    assert_eq!(body["data"]["expected_decimals"], 6);
+    let data = general_purpose::STANDARD.decode(body["data"]["instruction_data"].as_str().unwrap()).unwrap();
+    // MintToChecked is tag 14 and ends with the decimals byte
+    assert_eq!(data[0], 14);
+    assert_eq!(data[9], 6);
+
+    let (status, body) = post("/token/mint", json!({ "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": 100, "expected_decimals": 30 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid decimals value (max 9)");
+}
+
+#[tokio::test]
+async fn mint_token_rejects_bad_amounts() {
+    let (status, body) = post("/token/mint", json!({ "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": 0 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Amount must be greater than 0");
+
+    let (status, body) = post("/token/mint", json!({ "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": u64::MAX })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+}
+
+#[tokio::test]
+async fn send_token_uses_owner_ata_as_source() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let (status, body) = post("/send/token", json!({ "destination": pubkey(), "mint": mint.to_string(), "owner": owner.to_string(), "amount": 5 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["accounts"][0]["pubkey"], get_associated_token_address(&owner, &mint).to_string());
+}
+
+#[tokio::test]
+async fn send_token_rejects_sending_to_source() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let source = get_associated_token_address(&owner, &mint);
+    let (status, body) = post("/send/token", json!({ "destination": source.to_string(), "mint": mint.to_string(), "owner": owner.to_string(), "amount": 5 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Cannot send to same address");
+}
+
+#[tokio::test]
+async fn create_ata_returns_derived_address() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let (status, body) = post("/token/create-ata", json!({ "owner": owner.to_string(), "mint": mint.to_string(), "payer": pubkey() })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["associated_token_address"], get_associated_token_address(&owner, &mint).to_string());
+}
+
+#[tokio::test]
+async fn get_ata_returns_only_the_address() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let (status, body) = post("/token/ata", json!({ "owner": owner.to_string(), "mint": mint.to_string() })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"], json!({ "address": get_associated_token_address(&owner, &mint).to_string() }));
+
+    let (status, body) = post("/token/ata", json!({ "owner": "nope", "mint": mint.to_string() })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid owner address");
+}
+
+#[tokio::test]
+async fn create_ata_requires_owner() {
+    let (status, body) = post("/token/create-ata", json!({ "mint": pubkey(), "payer": pubkey() })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Missing required field: owner");
+}
+
+#[tokio::test]
+async fn approve_and_revoke() {
+    let (status, body) = post("/token/approve", json!({ "source": pubkey(), "delegate": pubkey(), "owner": pubkey(), "amount": 10 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = post("/token/approve", json!({ "source": pubkey(), "delegate": pubkey(), "owner": pubkey(), "amount": 0 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Amount must be greater than 0");
+
+    let (status, body) = post("/token/revoke", json!({ "source": pubkey(), "owner": pubkey() })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = post("/token/revoke", json!({ "source": pubkey() })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Missing required field: owner");
+}
+
+#[tokio::test]
+async fn close_account() {
+    let (status, body) = post("/token/close", json!({ "account": pubkey(), "destination": pubkey(), "owner": pubkey() })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let account = pubkey();
+    let (status, body) = post("/token/close", json!({ "account": account, "destination": account, "owner": pubkey() })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Cannot send to same address");
+}
+
+#[tokio::test]
+async fn checked_instructions_validate_decimals() {
+    let (status, body) = post("/token/transfer-checked", json!({ "source": pubkey(), "mint": pubkey(), "destination": pubkey(), "owner": pubkey(), "amount": 1, "decimals": 6 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = post("/token/transfer-checked", json!({ "source": pubkey(), "mint": pubkey(), "destination": pubkey(), "owner": pubkey(), "amount": 1, "decimals": 12 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid decimals value (max 9)");
+
+    let (status, body) = post("/token/mint-to-checked", json!({ "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": 1, "decimals": 6 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = post("/token/mint-to-checked", json!({ "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": 1 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Missing required field: decimals");
+}
+
+#[tokio::test]
+async fn set_authority() {
+    let (status, body) = post("/token/set-authority", json!({ "account": pubkey(), "current_authority": pubkey(), "new_authority": pubkey(), "authority_type": "MintTokens" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = post("/token/set-authority", json!({ "account": pubkey(), "current_authority": pubkey(), "authority_type": "Everything" })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+}
+
+#[tokio::test]
+async fn token_amount_conversions() {
+    let (status, body) = post("/token/amount/ui", json!({ "amount": 1_500_000, "decimals": 6 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["ui_amount"], 1.5);
+    assert_eq!(body["data"]["ui_amount_string"], "1.5");
+
+    let (status, body) = post("/token/amount/raw", json!({ "ui_amount": 1.5, "decimals": 6 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["amount"], 1_500_000);
+
+    let (status, body) = post("/token/amount/ui", json!({ "amount": 1, "decimals": 10 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid decimals value (max 9)");
+
+    let (status, body) = post("/token/amount/raw", json!({ "ui_amount": -1.0, "decimals": 6 })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+
+    let (status, body) = post("/token/amount/raw", json!({ "ui_amount": 1e30, "decimals": 9 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Amount too large");
+}
+
+#[tokio::test]
+async fn initialize_account_includes_rent() {
+    let owner = pubkey();
+    let (status, body) = post("/token/initialize-account", json!({ "account": pubkey(), "mint": pubkey(), "owner": owner })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["accounts"][2]["pubkey"], owner);
+    assert_eq!(body["data"]["rent_exempt_lamports"], Rent::default().minimum_balance(Account::LEN));
+
+    let (status, body) = post("/token/initialize-account", json!({ "account": pubkey(), "mint": pubkey() })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Missing required field: owner");
+}
+
+#[tokio::test]
+async fn sync_native_builds_instruction() {
+    let account = pubkey();
+    let (status, body) = post("/token/sync-native", json!({ "account": account })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["accounts"][0]["pubkey"], account);
+    assert_eq!(body["data"]["accounts"][0]["is_writable"], true);
+
+    let (status, body) = post("/token/sync-native", json!({ "account": "nope" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid account address");
+}
+
+#[tokio::test]
+async fn account_sizes_match_spl_layouts() {
+    let (status, body) = get("/token/account-sizes").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["mint_len"], 82);
+    assert_eq!(body["data"]["token_account_len"], 165);
+    assert_eq!(body["data"]["mint_rent_exempt"], Rent::default().minimum_balance(82));
+    assert_eq!(body["data"]["account_rent_exempt"], Rent::default().minimum_balance(165));
+}
+
+#[tokio::test]
+async fn freeze_and_thaw() {
+    let account = pubkey();
+    let body = json!({ "account": account, "mint": pubkey(), "authority": pubkey() });
+    let (status, frozen) = post("/token/freeze", body.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{}", frozen);
+    assert_eq!(frozen["data"]["accounts"][0]["pubkey"], account);
+
+    let (status, thawed) = post("/token/thaw", body).await;
+    assert_eq!(status, StatusCode::OK, "{}", thawed);
+    assert_ne!(frozen["data"]["instruction_data"], thawed["data"]["instruction_data"]);
+
+    let (status, body) = post("/token/freeze", json!({ "account": pubkey(), "mint": "bad", "authority": pubkey() })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid mint address");
+}
+
+// ---------------
+// messages
+
+#[tokio::test]
+async fn sign_then_verify_round_trip() {
+    let keypair = Keypair::new();
+    let secret = bs58::encode(keypair.to_bytes()).into_string();
+    let (status, body) = post("/message/sign", json!({ "message": "hello", "secret": secret })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["public_key"], keypair.pubkey().to_string());
+
+    let signature = body["data"]["signature"].clone();
+    let (status, body) = post("/message/verify", json!({ "message": "hello", "signature": signature, "pubkey": keypair.pubkey().to_string() })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["valid"], true);
+
+    let (_, body) = post("/message/verify", json!({ "message": "goodbye", "signature": signature, "pubkey": keypair.pubkey().to_string() })).await;
+    assert_eq!(body["data"]["valid"], false);
+}
+
+#[tokio::test]
+async fn hex_keys_round_trip() {
+    let keypair = Keypair::new();
+    let secret = format!("0x{}", encode_hex(&keypair.to_bytes()));
+    let pubkey = format!("0x{}", encode_hex(&keypair.pubkey().to_bytes()));
+
+    let (status, body) = post("/message/sign", json!({ "message": "hello", "secret": secret })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["public_key"], keypair.pubkey().to_string());
+
+    let (status, body) = post("/message/sign", json!({ "message": "hello", "secret": encode_hex(&keypair.to_bytes()), "encoding": "hex" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let signature = format!("0x{}", encode_hex(keypair.sign_message(b"hello").as_ref()));
+    let (status, body) = post("/message/verify", json!({ "message": "hello", "signature": signature, "pubkey": pubkey })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["valid"], true);
+
+    let (_, body) = post("/pubkey/validate", json!({ "pubkey": pubkey })).await;
+    assert_eq!(body["data"]["valid"], true);
+
+    let (_, body) = post("/pubkey/validate", json!({ "pubkey": "0xabcd" })).await;
+    assert_eq!(body["data"]["valid"], false);
+}
+
+#[tokio::test]
+async fn sign_with_nonce_and_timestamp() {
+    let keypair = Keypair::new();
+    let secret = bs58::encode(keypair.to_bytes()).into_string();
+    let (status, body) = post("/message/sign", json!({ "message": "login", "secret": secret, "nonce": "abc123", "timestamp": 1700000000 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["signed_payload"], "abc123:1700000000:login");
+
+    let signature = body["data"]["signature"].clone();
+    let verify = |nonce: &str| json!({ "message": "login", "signature": signature, "pubkey": keypair.pubkey().to_string(), "nonce": nonce, "timestamp": 1700000000 });
+    let (_, body) = post("/message/verify", verify("abc123")).await;
+    assert_eq!(body["data"]["valid"], true);
+    let (_, body) = post("/message/verify", verify("other")).await;
+    assert_eq!(body["data"]["valid"], false);
+
+    let (status, body) = post("/message/sign", json!({ "message": "login", "secret": secret, "nonce": "abc123" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "nonce and timestamp must be provided together");
+
+    let (status, _) = post("/message/sign", json!({ "message": "login", "secret": secret, "nonce": "a:b", "timestamp": 1 })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn replay_protection_rejects_reused_nonce() {
+    let keypair = Keypair::new();
+    let signature = keypair.sign_message(b"n1:1700000000:login").to_string();
+    let request = || axum::http::Request::post("/message/verify")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "message": "login", "signature": signature, "pubkey": keypair.pubkey().to_string(), "signatureEncoding": "base58", "nonce": "n1", "timestamp": 1700000000 }).to_string()))
+        .unwrap();
+
+    let config = Config { replay_protection: true, ..Config::default() };
+    let app = app(config).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))));
+
+    let response = app.clone().oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.clone().oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(body["code"], "REPLAY_DETECTED");
+
+    // stateless by default: the same request verifies every time
+    let (status, _) = send(request()).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(request()).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[test]
+fn expired_nonces_can_be_reused_and_are_pruned() {
+    let state = AppState::new(Config { replay_protection: true, nonce_ttl: Duration::ZERO, ..Config::default() });
+    let key = Pubkey::new_unique();
+    state.consume_nonce(key, "n").unwrap();
+    state.consume_nonce(key, "n").unwrap();
+    state.prune_nonces();
+    assert!(state.seen_nonces.is_empty());
+}
+
+#[tokio::test]
+async fn sign_can_return_both_encodings() {
+    let keypair = Keypair::new();
+    let secret = keypair.to_base58_string();
+    let expected = keypair.sign_message(b"hello");
+
+    let (status, body) = post("/message/sign", json!({ "message": "hello", "secret": secret, "bothEncodings": true })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["signature"], general_purpose::STANDARD.encode(expected.as_ref()));
+    assert_eq!(body["data"]["signature_base64"], body["data"]["signature"]);
+    assert_eq!(body["data"]["signature_base58"], expected.to_string());
+
+    let (_, body) = post("/message/sign", json!({ "message": "hello", "secret": secret })).await;
+    assert!(body["data"].get("signature_base58").is_none());
+}
+
+#[tokio::test]
+async fn blank_messages_are_rejected() {
+    let keypair = Keypair::new();
+    let secret = keypair.to_base58_string();
+    let signature = keypair.sign_message(b" ").to_string();
+    for message in ["", "   ", "\n"] {
+        let (status, body) = post("/message/sign", json!({ "message": message, "secret": secret })).await;
+        assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "message cannot be empty");
+
+        let (status, body) = post("/message/verify", json!({ "message": message, "signature": signature, "pubkey": keypair.pubkey().to_string(), "signatureEncoding": "base58" })).await;
+        assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "message cannot be empty");
+    }
+
+    let (status, body) = post("/message/sign/batch", json!({ "messages": ["ok", " "], "secret": secret })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "messages[1]: message cannot be empty");
+}
+
+#[tokio::test]
+async fn sign_domain_is_prepended_to_signed_bytes() {
+    let keypair = Keypair::new();
+    let config = Config { sign_domain: Some("myapp".to_string()), ..Config::default() };
+    let app = app(config).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))));
+    let request = |path: &str, body: Value| axum::http::Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let response = app.clone().oneshot(request("/message/sign", json!({ "message": "hello", "secret": keypair.to_base58_string(), "signatureEncoding": "base58" }))).await.unwrap();
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    let signature = body["data"]["signature"].as_str().unwrap().to_string();
+    assert_eq!(signature, keypair.sign_message(b"myapp\0hello").to_string());
+
+    let verify = json!({ "message": "hello", "signature": signature, "pubkey": keypair.pubkey().to_string(), "signatureEncoding": "base58" });
+    let response = app.oneshot(request("/message/verify", verify.clone())).await.unwrap();
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(body["data"]["valid"], true);
+
+    // without the domain it's a signature over different bytes
+    let (_, body) = post("/message/verify", verify).await;
+    assert_eq!(body["data"]["valid"], false);
+}
+
+#[tokio::test]
+async fn sign_accepts_id_json_and_base58_secrets_alike() {
+    let keypair = Keypair::new();
+    let id_json = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+
+    let (status, from_array) = post("/message/sign", json!({ "message": "hello", "secret": id_json })).await;
+    assert_eq!(status, StatusCode::OK, "{}", from_array);
+    let (status, from_base58) = post("/message/sign", json!({ "message": "hello", "secret": keypair.to_base58_string() })).await;
+    assert_eq!(status, StatusCode::OK, "{}", from_base58);
+
+    let decode = |body: &Value| general_purpose::STANDARD.decode(body["data"]["signature"].as_str().unwrap()).unwrap();
+    assert_eq!(decode(&from_array), decode(&from_base58));
+    assert_eq!(decode(&from_array), keypair.sign_message(b"hello").as_ref());
+
+    let short = serde_json::to_string(&keypair.to_bytes()[..63].to_vec()).unwrap();
+    let (status, body) = post("/message/sign", json!({ "message": "hello", "secret": short })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid secret key");
+}
+
+#[tokio::test]
+async fn markup_in_messages_is_only_refused_with_strict_input() {
+    let body = json!({ "message": "<script>alert(1)</script>", "secret": Keypair::new().to_base58_string() });
+    for (strict_input, expected) in [(false, StatusCode::OK), (true, StatusCode::UNPROCESSABLE_ENTITY)] {
+        let app = app(Config { strict_input, ..Config::default() }).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))));
+        let response = app.oneshot(axum::http::Request::post("/message/sign")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()).await.unwrap();
+        let status = response.status();
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(status, expected, "strict_input={}: {}", strict_input, body);
+        if strict_input {
+            assert_eq!(body["code"], "SUSPICIOUS_INPUT");
+        } else {
+            assert_eq!(body["data"]["message"], "<script>alert(1)</script>");
+        }
+    }
+}
+
+#[tokio::test]
+async fn sign_rejects_bad_secret() {
+    let (status, body) = post("/message/sign", json!({ "message": "hello", "secret": "not-a-key" })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+
+    let (one, other) = (Keypair::new(), Keypair::new());
+    let mut mismatched = one.to_bytes();
+    mismatched[32..].copy_from_slice(other.pubkey().as_ref());
+    let secrets = [
+        // base58 of the right alphabet but the wrong length
+        bs58::encode(&one.to_bytes()[..40]).into_string(),
+        serde_json::to_string(&one.to_bytes()[..50].to_vec()).unwrap(),
+        bs58::encode(mismatched).into_string(),
+    ];
+    for secret in secrets {
+        let (status, body) = post("/message/sign", json!({ "message": "hello", "secret": secret })).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+        // no 8-character run of the secret may show up anywhere in the response
+        let body = body.to_string();
+        let chars: Vec<char> = secret.chars().collect();
+        for window in chars.windows(8) {
+            let window: String = window.iter().collect();
+            assert!(!body.contains(&window), "{} leaked into {}", window, body);
+        }
+    }
+
+    let (status, body) = post("/message/sign", json!({ "secret": "x" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Missing required field: message");
+}
+
+#[tokio::test]
+async fn verify_schemes_agree() {
+    let keypair = Keypair::new();
+    let signature = general_purpose::STANDARD.encode(keypair.sign_message(b"hello").as_ref());
+    let verify = |message: &str, scheme: &str| json!({ "message": message, "signature": signature, "pubkey": keypair.pubkey().to_string(), "scheme": scheme });
+
+    for scheme in ["solana", "ed25519"] {
+        let (status, body) = post("/message/verify", verify("hello", scheme)).await;
+        assert_eq!(status, StatusCode::OK, "{}: {}", scheme, body);
+        assert_eq!(body["data"]["valid"], true, "{}", scheme);
+
+        let (status, body) = post("/message/verify", verify("hellp", scheme)).await;
+        assert_eq!(status, StatusCode::OK, "{}: {}", scheme, body);
+        assert_eq!(body["data"]["valid"], false, "{}", scheme);
+    }
+
+    let (status, body) = post("/message/verify", verify("hello", "secp256k1")).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Unsupported scheme 'secp256k1', expected solana or ed25519");
+}
+
+#[tokio::test]
+async fn verify_accepts_base58_signatures() {
+    let keypair = Keypair::new();
+    let signature = keypair.sign_message(b"hello").to_string();
+    let (status, body) = post("/message/verify", json!({ "message": "hello", "signature": signature, "pubkey": keypair.pubkey().to_string(), "signatureEncoding": "base58" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["valid"], true);
+
+    let (status, body) = post("/message/verify", json!({ "message": "hello", "signature": signature, "pubkey": keypair.pubkey().to_string(), "signatureEncoding": "hex" })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+}
+
+#[tokio::test]
+async fn sign_then_verify_in_base58() {
+    let keypair = Keypair::new();
+    let secret = keypair.to_base58_string();
+    let (status, body) = post("/message/sign", json!({ "message": "hello", "secret": secret, "signatureEncoding": "base58" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let signature = body["data"]["signature"].as_str().unwrap();
+    assert_eq!(signature, keypair.sign_message(b"hello").to_string());
+
+    let (status, body) = post("/message/verify", json!({ "message": "hello", "signature": signature, "pubkey": keypair.pubkey().to_string(), "signatureEncoding": "base58" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["valid"], true);
+
+    let (status, body) = post("/message/sign", json!({ "message": "hello", "secret": secret, "signatureEncoding": "utf8" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Unsupported signatureEncoding 'utf8', expected base58, base64 or hex");
+}
+
+// s + L, the same signature with its scalar pushed past the group order
+fn malleate(signature: &Signature) -> String {
+    const L: [u8; 32] = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+    ];
+    let mut bytes: [u8; 64] = signature.as_ref().try_into().unwrap();
+    let mut carry = 0u16;
+    for (i, l) in L.iter().enumerate() {
+        let sum = bytes[32 + i] as u16 + *l as u16 + carry;
+        bytes[32 + i] = sum as u8;
+        carry = sum >> 8;
+    }
+    bs58::encode(bytes).into_string()
+}
+
+#[tokio::test]
+async fn strict_verify_rejects_non_canonical_signatures() {
+    let keypair = Keypair::new();
+    let signature = keypair.sign_message(b"hello");
+    let request = |signature: String, strict: bool| json!({ "message": "hello", "signature": signature, "pubkey": keypair.pubkey().to_string(), "signatureEncoding": "base58", "strict": strict });
+
+    let (status, body) = post("/message/verify", request(signature.to_string(), true)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["valid"], true);
+
+    let (status, body) = post("/message/verify", request(malleate(&signature), true)).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Signature is not in canonical form");
+    assert_eq!(body["code"], "NON_CANONICAL_SIGNATURE");
+
+    // lenient mode just reports it as not valid
+    let (status, body) = post("/message/verify", request(malleate(&signature), false)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["valid"], false);
+}
+
+#[tokio::test]
+async fn sign_rejects_mismatched_key_halves() {
+    let mut bytes = Keypair::new().to_bytes();
+    bytes[32..].copy_from_slice(&Keypair::new().pubkey().to_bytes());
+    let (status, body) = post("/message/sign", json!({ "message": "hello", "secret": bs58::encode(bytes).into_string() })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Secret key does not match its embedded public key");
+}
+
+#[tokio::test]
+async fn verify_rejects_bad_signature() {
+    let (status, body) = post("/message/verify", json!({ "message": "hello", "signature": "!!!", "pubkey": pubkey() })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid signature format");
+}
+
+#[tokio::test]
+async fn sign_batch_and_multi() {
+    let keypair = Keypair::new();
+    let secret = bs58::encode(keypair.to_bytes()).into_string();
+    let (status, body) = post("/message/sign/batch", json!({ "secret": secret, "messages": ["a", "b"] })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["signatures"].as_array().unwrap().len(), 2);
+
+    let (status, body) = post("/message/sign/batch", json!({ "secret": secret, "messages": [] })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "messages must not be empty");
+
+    let other = bs58::encode(Keypair::new().to_bytes()).into_string();
+    let (status, body) = post("/message/sign/multi", json!({ "message": "m", "secrets": [secret, other] })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["signatures"].as_array().unwrap().len(), 2);
+
+    let (status, body) = post("/message/sign/multi", json!({ "message": "m", "secrets": [secret, "bad"] })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+    assert!(body["error"].as_str().unwrap().starts_with("secrets[1]"));
+}
+
+#[tokio::test]
+async fn collection_caps_name_the_limit() {
+    let secrets: Vec<String> = (0..=MAX_SIGNERS).map(|_| bs58::encode(Keypair::new().to_bytes()).into_string()).collect();
+    let (status, body) = post("/message/sign/multi", json!({ "message": "m", "secrets": secrets })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Too many secrets (max 11)");
+
+    let recipients: Vec<Value> = (0..=MAX_RECIPIENTS).map(|_| json!({ "to": pubkey(), "lamports": 1 })).collect();
+    let (status, body) = post("/send/sol/multi", json!({ "from": pubkey(), "recipients": recipients })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Too many recipients (max 21)");
+
+    let seeds = vec!["s"; MAX_SEEDS];
+    let (status, body) = post("/pda/derive", json!({ "program_id": pubkey(), "seeds": seeds })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Too many seeds (max 15)");
+}
+
+#[test]
+fn max_recipients_fills_one_transaction() {
+    let from = Keypair::new().pubkey();
+    let size = |n: usize| {
+        let transfers: Vec<Instruction> = (0..n).map(|_| system_instruction::transfer(&from, &Keypair::new().pubkey(), 1)).collect();
+        bincode::serialize(&Transaction::new_with_payer(&transfers, Some(&from))).unwrap().len()
+    };
+    assert!(size(MAX_RECIPIENTS) <= PACKET_DATA_SIZE);
+    assert!(size(MAX_RECIPIENTS + 1) > PACKET_DATA_SIZE);
+}
+
+#[tokio::test]
+async fn verify_batch() {
+    let keypair = Keypair::new();
+    let signature = general_purpose::STANDARD.encode(keypair.sign_message(b"m").as_ref());
+    let item = json!({ "message": "m", "signature": signature, "pubkey": keypair.pubkey().to_string() });
+    let (status, body) = post("/message/verify/batch", json!({ "items": [item, item] })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"][1]["valid"], true);
+
+    let (status, body) = post("/message/verify/batch", json!({ "items": [] })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "items must not be empty");
+}
+
+#[tokio::test]
+async fn verify_any_reports_matching_candidates() {
+    let keypair = Keypair::new();
+    let signer = keypair.pubkey().to_string();
+    let signature = general_purpose::STANDARD.encode(keypair.sign_message(b"m").as_ref());
+
+    let (status, body) = post("/message/verify/any", json!({ "message": "m", "signature": signature, "pubkeys": [pubkey(), signer, pubkey()] })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["valid"], true);
+    assert_eq!(body["data"]["matches"], json!([signer]));
+
+    let (status, body) = post("/message/verify/any", json!({ "message": "m", "signature": signature, "pubkeys": [pubkey()] })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["valid"], false);
+    assert_eq!(body["data"]["matches"], json!([]));
+
+    let (status, body) = post("/message/verify/any", json!({ "message": "m", "signature": signature, "pubkeys": [signer, "nope"] })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "pubkeys[1]: Invalid public key");
+}
+
+#[tokio::test]
+async fn denylisted_addresses_are_refused() {
+    let blocked = Pubkey::new_unique();
+    let config = Config { denylist: HashSet::from([blocked]), ..Config::default() };
+    let app = app(config).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))));
+    let request = |path: &str, body: Value| axum::http::Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let cases = [
+        ("/send/sol", json!({ "from": pubkey(), "to": blocked.to_string(), "lamports": 1000 })),
+        ("/send/token", json!({ "destination": pubkey(), "mint": blocked.to_string(), "owner": pubkey(), "amount": 5 })),
+        ("/token/mint", json!({ "mint": pubkey(), "destination": pubkey(), "authority": blocked.to_string(), "amount": 5 })),
+        ("/token/mint-to-checked", json!({ "mint": pubkey(), "destination": blocked.to_string(), "authority": pubkey(), "amount": 5, "decimals": 6 })),
+        ("/token/transfer-checked", json!({ "source": pubkey(), "mint": pubkey(), "destination": pubkey(), "owner": blocked.to_string(), "amount": 5, "decimals": 6 })),
+        ("/token/approve", json!({ "source": pubkey(), "delegate": blocked.to_string(), "owner": pubkey(), "amount": 5 })),
+    ];
+    for (path, body) in cases {
+        let response = app.clone().oneshot(request(path, body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{}", path);
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["error"], "address not permitted");
+        assert_eq!(body["code"], "ADDRESS_NOT_PERMITTED");
+    }
+
+    let response = app.oneshot(request("/send/sol", json!({ "from": pubkey(), "to": pubkey(), "lamports": 1000 }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+// ---------------
+// SOL transfers and transactions
+
+#[tokio::test]
+async fn send_sol_builds_transfer() {
+    let from = pubkey();
+    let (status, body) = post("/send/sol", json!({ "from": from, "to": pubkey(), "lamports": 1000 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    // same account meta shape as the token endpoints
+    assert_eq!(body["data"]["accounts"][0]["pubkey"], from);
+    assert_eq!(body["data"]["accounts"][0]["role"], "from");
+    assert_eq!(body["data"]["accounts"][0]["is_signer"], true);
+    assert_eq!(body["data"]["accounts"][1]["is_signer"], false);
+    assert_eq!(body["data"]["accounts"][1]["is_writable"], true);
+    assert!(body["data"].get("instructions").is_none());
+
+    let (status, body) = post("/send/sol", json!({ "from": from, "to": pubkey(), "lamports": 1000, "memo": "rent" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["instructions"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn amounts_may_be_numeric_strings() {
+    let big = u64::MAX / 4;
+    let (status, body) = post("/token/mint", json!({ "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": big.to_string() })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let data = general_purpose::STANDARD.decode(body["data"]["instruction_data"].as_str().unwrap()).unwrap();
+    assert_eq!(data[1..9], big.to_le_bytes());
+
+    let (status, body) = post("/send/sol", json!({ "from": pubkey(), "to": pubkey(), "lamports": "1000" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = post("/send/token", json!({ "destination": pubkey(), "mint": pubkey(), "owner": pubkey(), "amount": "5" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    for bad in ["1.5", "-1", "+1", "", "18446744073709551616"] {
+        let (status, body) = post("/send/sol", json!({ "from": pubkey(), "to": pubkey(), "lamports": bad })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", bad, body);
+        assert_eq!(body["code"], "INVALID_JSON_DATA");
+    }
+}
+
+#[tokio::test]
+async fn send_sol_validates_input() {
+    let from = pubkey();
+    let (status, body) = post("/send/sol", json!({ "from": from, "to": from, "lamports": 1000 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Cannot send to same address");
+
+    let (status, body) = post("/send/sol", json!({ "from": from, "to": pubkey(), "lamports": 0 })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Amount must be greater than 0");
+}
+
+#[tokio::test]
+async fn send_sol_limit_and_self_transfer_are_configurable() {
+    let from = pubkey();
+    let (status, body) = post("/send/sol", json!({ "from": from, "to": from, "lamports": 1000, "allow_self_transfer": true })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["accounts"][0]["pubkey"], body["data"]["accounts"][1]["pubkey"]);
+
+    let config = Config { max_transfer_lamports: 1_000_000_000, ..Config::default() };
+    let response = app(config)
+        .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))))
+        .oneshot(axum::http::Request::post("/send/sol")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!({ "from": from, "to": pubkey(), "lamports": 1_000_000_001u64 }).to_string()))
+            .unwrap())
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(body["error"], "Amount too large (max 1000000000)");
+}
+
+#[tokio::test]
+async fn send_sol_multi_builds_one_transfer_per_recipient() {
+    let from = pubkey();
+    let (a, b) = (pubkey(), pubkey());
+    let (status, body) = post("/send/sol/multi", json!({ "from": from, "recipients": [{ "to": a, "lamports": 10 }, { "to": b, "lamports": 20 }] })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let instructions = body["data"]["instructions"].as_array().unwrap();
+    assert_eq!(instructions.len(), 2);
+    assert_eq!(instructions[0]["accounts"][1]["pubkey"], a);
+    assert_eq!(instructions[1]["accounts"][1]["pubkey"], b);
+    assert_eq!(body["data"]["total_lamports"], 30);
+}
+
+#[tokio::test]
+async fn send_sol_multi_validates_recipients() {
+    let from = pubkey();
+    let to = pubkey();
+    let (status, body) = post("/send/sol/multi", json!({ "from": from, "recipients": [{ "to": to, "lamports": 1 }, { "to": to, "lamports": 1 }] })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "recipients[1]: Duplicate recipient");
+
+    let (status, body) = post("/send/sol/multi", json!({ "from": from, "recipients": [{ "to": from, "lamports": 1 }] })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "recipients[0]: Cannot send to same address");
+
+    let max = MAX_TRANSFER_LAMPORTS;
+    let (status, body) = post("/send/sol/multi", json!({ "from": from, "recipients": [{ "to": pubkey(), "lamports": max }, { "to": pubkey(), "lamports": 1 }] })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, &format!("recipients[1]: Total amount too large (max {})", max));
+
+    let (status, body) = post("/send/sol/multi", json!({ "from": from, "recipients": [] })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "recipients must not be empty");
+}
+
+#[tokio::test]
+async fn transfer_with_ata_creates_then_transfers() {
+    let (owner, recipient, mint, payer) = (Keypair::new().pubkey(), Keypair::new().pubkey(), Keypair::new().pubkey(), pubkey());
+    let source = get_associated_token_address(&owner, &mint).to_string();
+    let ata = get_associated_token_address(&recipient, &mint).to_string();
+    let (status, body) = post("/token/transfer", json!({ "source": source, "mint": mint.to_string(), "owner": owner.to_string(), "recipient_wallet": recipient.to_string(), "amount": "250", "decimals": 6, "payer": payer })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["recipient_token_account"], ata);
+
+    let instructions = body["data"]["instructions"].as_array().unwrap();
+    assert_eq!(instructions.len(), 2);
+    assert_eq!(instructions[0]["program_id"], spl_associated_token_account::id().to_string());
+    // CreateIdempotent is tag 1 of the associated token program
+    assert_eq!(general_purpose::STANDARD.decode(instructions[0]["instruction_data"].as_str().unwrap()).unwrap(), vec![1]);
+    assert_eq!(instructions[1]["program_id"], spl_token::id().to_string());
+    assert_eq!(instructions[1]["accounts"][2]["pubkey"], ata);
+
+    // sending from the recipient's own token account is a self-transfer
+    let (status, body) = post("/token/transfer", json!({ "source": ata, "mint": mint.to_string(), "owner": owner.to_string(), "recipient_wallet": recipient.to_string(), "amount": 1, "decimals": 6, "payer": payer })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Cannot send to same address");
+
+    let (status, body) = post("/token/transfer", json!({ "source": source, "mint": mint.to_string(), "owner": owner.to_string(), "recipient_wallet": recipient.to_string(), "amount": 1, "payer": payer })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Missing required field: decimals");
+}
+
+#[tokio::test]
+async fn send_sol_multi_rejects_overflowing_totals() {
+    let config = Config { max_transfer_lamports: u64::MAX, ..Config::default() };
+    let app = app(config).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))));
+    let recipients = json!([{ "to": pubkey(), "lamports": u64::MAX - 1 }, { "to": pubkey(), "lamports": 2 }]);
+    let request = axum::http::Request::post("/send/sol/multi")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "from": pubkey(), "recipients": recipients }).to_string()))
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "recipients[1]: amount sum overflow");
+    assert_eq!(body["code"], "INVALID_AMOUNT");
+}
+
+#[tokio::test]
+async fn serialize_instruction_round_trips_a_builder_response() {
+    let (from, to) = (Keypair::new().pubkey(), Keypair::new().pubkey());
+    let (status, body) = post("/send/sol", json!({ "from": from.to_string(), "to": to.to_string(), "lamports": 5000 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = post("/instruction/serialize", body["data"].clone()).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let bytes = general_purpose::STANDARD.decode(body["data"]["instruction"].as_str().unwrap()).unwrap();
+    assert_eq!(body["data"]["length"], bytes.len());
+    let instruction: Instruction = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(instruction, system_instruction::transfer(&from, &to, 5000));
+}
+
+#[tokio::test]
+async fn serialize_instruction_validates_input() {
+    let account = json!({ "pubkey": pubkey(), "is_signer": true, "is_writable": true });
+    let (status, body) = post("/instruction/serialize", json!({ "program_id": pubkey(), "accounts": [account, { "pubkey": "nope" }], "instruction_data": "" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "accounts[1]: Invalid account pubkey");
+
+    let (status, body) = post("/instruction/serialize", json!({ "program_id": pubkey(), "accounts": [], "instruction_data": "!!" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid instruction data");
+
+    let (status, body) = post("/instruction/serialize", json!({ "program_id": pubkey(), "accounts": [], "instruction_data": "AQI=", "data_sha256": "00" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "instruction_data does not match data_sha256");
+
+    // no accounts and no data is still a valid instruction
+    let (status, body) = post("/instruction/serialize", json!({ "program_id": pubkey(), "accounts": [], "instruction_data": "" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+#[tokio::test]
+async fn create_account_builds_system_instruction() {
+    let (status, body) = post("/system/create-account", json!({ "payer": pubkey(), "new_account": pubkey(), "lamports": 1_000_000, "space": 82, "owner": spl_token::id().to_string() })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["accounts"][1]["is_signer"], true);
+
+    let (status, body) = post("/system/create-account", json!({ "payer": pubkey(), "new_account": pubkey(), "lamports": 1, "space": u64::MAX, "owner": pubkey() })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+}
+
+#[tokio::test]
+async fn decode_transaction_round_trip() {
+    let payer = Keypair::new();
+    let to = Pubkey::new_unique();
+    let instruction = system_instruction::transfer(&payer.pubkey(), &to, 42);
+    let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    let encoded = general_purpose::STANDARD.encode(bincode::serialize(&transaction).unwrap());
+
+    let (status, body) = post("/transaction/decode", json!({ "transaction": encoded })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["fee_payer"], payer.pubkey().to_string());
+    assert_eq!(body["data"]["instructions"].as_array().unwrap().len(), 1);
+
+    let (status, body) = post("/transaction/decode", json!({ "transaction": "AAAA" })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "Invalid transaction");
+}
+
+#[tokio::test]
+async fn batch_runs_operations_in_order() {
+    let (status, body) = post("/batch", json!({ "operations": [
+        { "type": "create_token", "params": { "mintAuthority": pubkey(), "mint": pubkey(), "decimals": 6 } },
+        { "type": "mint", "params": { "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": 10 } },
+        { "type": "transfer", "params": { "destination": pubkey(), "mint": pubkey(), "owner": pubkey(), "amount": 5 } },
+        { "type": "send_sol", "params": { "from": pubkey(), "to": pubkey(), "lamports": 100, "memo": "hi" } },
+    ] })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let results = body["data"].as_array().unwrap();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[1]["type"], "mint");
+    assert_eq!(results[3]["instructions"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn batch_reports_failing_operation() {
+    let (status, body) = post("/batch", json!({ "operations": [
+        { "type": "mint", "params": { "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": 10 } },
+        { "type": "mint", "params": { "mint": pubkey(), "destination": pubkey(), "authority": pubkey(), "amount": 0 } },
+    ] })).await;
+    assert_error(status, &body, StatusCode::UNPROCESSABLE_ENTITY, "operations[1]: Amount must be greater than 0");
+    // the item prefix doesn't hide the underlying category
+    assert_eq!(body["code"], "INVALID_AMOUNT");
+
+    let (status, body) = post("/batch", json!({ "operations": [{ "type": "burn", "params": {} }] })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+    assert!(body["error"].as_str().unwrap().starts_with("operations[0]: Unsupported operation type"));
+}
+
+// ---------------
+// addresses
+
+#[tokio::test]
+async fn validate_pubkey_reports_curve() {
+    let (status, body) = post("/pubkey/validate", json!({ "pubkey": Keypair::new().pubkey().to_string() })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["valid"], true);
+    assert_eq!(body["data"]["on_curve"], true);
+
+    let (_, body) = post("/pubkey/validate", json!({ "pubkey": "not-a-key" })).await;
+    assert_eq!(body["data"]["valid"], false);
+}
+
+#[test]
+fn pubkey_length_precheck_keeps_edge_cases() {
+    assert!(is_valid_pubkey(&Pubkey::default().to_string()));
+    assert_eq!(Pubkey::default().to_string().len(), 32);
+    assert!(is_valid_pubkey(&Pubkey::new_from_array([255; 32]).to_string()));
+    assert!(!is_valid_pubkey(&"1".repeat(31)));
+    assert!(!is_valid_pubkey(&"z".repeat(45)));
+    assert!(!is_valid_pubkey(""));
+}
+
+#[tokio::test]
+async fn derive_pda_matches_sdk() {
+    let program_id = Pubkey::new_unique();
+    let (expected, bump) = Pubkey::find_program_address(&[b"vault"], &program_id);
+    let (status, body) = post("/pda/derive", json!({ "program_id": program_id.to_string(), "seeds": ["vault"] })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["address"], expected.to_string());
+    assert_eq!(body["data"]["bump"], bump);
+
+    let (status, body) = post("/pda/derive", json!({ "program_id": program_id.to_string(), "seeds": ["x".repeat(40)] })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+}
+
+
+// ---------------
+// input helpers, property tests with random and adversarial strings
+
+proptest! {
+    #[test]
+    fn input_helpers_never_panic(s in any::<String>()) {
+        let state = AppState::new(Config::default());
+        is_valid_base64(&state, &s);
+        is_valid_pubkey(&s);
+        is_suspicious_text(&s, false);
+        is_suspicious_text(&s, true);
+        decode_hex(&s);
+        for encoding in [None, Some("base58"), Some("base64"), Some("hex"), Some("utf8")] {
+            let _ = decode_bytes(&state, &s, encoding, "base58", "bad");
+        }
+    }
+
+    #[test]
+    fn base64_padding_anomalies_are_rejected_not_panics(s in "[A-Za-z0-9+/]{0,12}={0,4}[A-Za-z0-9+/=]{0,4}") {
+        let state = AppState::new(Config::default());
+        if is_valid_base64(&state, &s) {
+            prop_assert_eq!(state.base64.encode(state.base64.decode(&s).unwrap()), s);
+        }
+    }
+
+    #[test]
+    fn hex_round_trips(bytes in prop::collection::vec(any::<u8>(), 1..64)) {
+        prop_assert_eq!(decode_hex(&encode_hex(&bytes)), Some(bytes));
+    }
+
+    #[test]
+    fn decode_hex_only_accepts_hex_digits(s in "[0-9a-fA-F+\\- ]{0,16}") {
+        if decode_hex(&s).is_some() {
+            prop_assert!(s.chars().all(|c| c.is_ascii_hexdigit()), "accepted {:?}", s);
+        }
+    }
+
+    #[test]
+    fn pubkeys_round_trip_in_every_encoding(bytes in any::<[u8; 32]>()) {
+        let key = Pubkey::new_from_array(bytes);
+        let hex = format!("0x{}", encode_hex(&bytes));
+        prop_assert!(is_valid_pubkey(&key.to_string()));
+        prop_assert!(is_valid_pubkey(&hex));
+        prop_assert_eq!(parse_pubkey(&hex, "bad").unwrap(), key);
+    }
+
+    #[test]
+    fn wrong_length_keys_are_invalid(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        prop_assume!(bytes.len() != 32);
+        let hex = format!("0x{}", encode_hex(&bytes));
+        prop_assert!(!is_valid_pubkey(&bs58::encode(&bytes).into_string()));
+        prop_assert!(!is_valid_pubkey(&hex));
+    }
+
+    #[test]
+    fn printable_text_within_limit_is_accepted(s in "[^\\p{Cc}]{1,1000}") {
+        prop_assume!(!s.trim().is_empty());
+        prop_assert!(!is_suspicious_text(&s, false));
+    }
+}